@@ -0,0 +1,137 @@
+// Copyright 2013 Simon Sapin.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A C-compatible ABI around the core parser, so that the crate can be
+//! embedded in non-Rust hosts.  A parsed URL is handed back as an opaque
+//! `*URL` handle that must eventually be released with `url_free`; the
+//! serialization and component accessors copy into a caller-provided buffer
+//! and report the length that was (or would have been) written.
+
+use std::cast;
+use std::libc::{c_char, c_int, size_t};
+use std::ptr;
+use std::str;
+
+use super::{URL, ParseError};
+use super::{EmptyHost, InvalidScheme, InvalidPort, InvalidIpv4Address,
+            InvalidIpv6Address, InvalidDomainCharacter, InvalidCharacter,
+            InvalidBackslash, InvalidPercentEncoded, InvalidAtSymbolInUser,
+            ExpectedTwoSlashes, ExpectedInitialSlash, IdnaError,
+            RelativeUrlWithCannotBeABaseBase, RelativeUrlWithoutBase};
+
+
+// A distinct negative status code for each `ParseError` variant, so the host
+// can map a failure to its own error enum without string matching.
+fn error_code(error: ParseError) -> c_int {
+    match error {
+        EmptyHost => -1,
+        InvalidScheme => -2,
+        InvalidPort => -3,
+        InvalidIpv4Address => -4,
+        InvalidIpv6Address => -5,
+        InvalidDomainCharacter => -6,
+        InvalidCharacter => -7,
+        InvalidBackslash => -8,
+        InvalidPercentEncoded => -9,
+        InvalidAtSymbolInUser => -10,
+        ExpectedTwoSlashes => -11,
+        ExpectedInitialSlash => -12,
+        IdnaError => -13,
+        RelativeUrlWithCannotBeABaseBase => -14,
+        RelativeUrlWithoutBase => -15,
+    }
+}
+
+
+// Copy `string` into the caller's NUL-terminated buffer (truncating if it does
+// not fit) and return the length the full string would need, excluding the
+// terminator.
+fn copy_to_buffer(string: &str, buffer: *mut c_char, capacity: size_t) -> size_t {
+    let bytes = string.as_bytes();
+    let length = bytes.len();
+    if buffer.is_not_null() && capacity > 0 {
+        let n = (capacity as uint - 1).min(&length);
+        unsafe {
+            ptr::copy_memory(buffer as *mut u8, bytes.as_ptr(), n);
+            *(buffer.offset(n as int)) = 0;
+        }
+    }
+    length as size_t
+}
+
+
+/// Parse `input` (a NUL-terminated UTF-8 string) against an optional `base`
+/// handle.  On success the parsed URL is stored in `*out` and `0` is returned;
+/// on failure a negative `error_code` is returned and `*out` is left untouched.
+#[no_mangle]
+pub extern "C" fn url_parse(input: *c_char, base: *URL, out: *mut *mut URL) -> c_int {
+    let input = unsafe { str::raw::from_c_str(input) };
+    let base_url = if base.is_null() { None } else { Some(unsafe { &*base }) };
+    match URL::parse(input, base_url) {
+        Ok(url) => {
+            unsafe { *out = cast::transmute::<~URL, *mut URL>(~url); }
+            0
+        },
+        Err(error) => error_code(error),
+    }
+}
+
+/// Release a handle previously returned by `url_parse`.
+#[no_mangle]
+pub extern "C" fn url_free(url: *mut URL) {
+    if url.is_not_null() {
+        let _: ~URL = unsafe { cast::transmute(url) };
+    }
+}
+
+/// Write the full serialization of `url` into `buffer`.
+#[no_mangle]
+pub extern "C" fn url_serialize(url: *URL, buffer: *mut c_char, capacity: size_t)
+                             -> size_t {
+    copy_to_buffer(unsafe { &*url }.serialize(), buffer, capacity)
+}
+
+/// Write the scheme of `url` into `buffer`.
+#[no_mangle]
+pub extern "C" fn url_scheme(url: *URL, buffer: *mut c_char, capacity: size_t)
+                          -> size_t {
+    copy_to_buffer(unsafe { &*url }.scheme, buffer, capacity)
+}
+
+/// Write the host of `url` into `buffer`, or the empty string for a
+/// cannot-be-a-base URL.
+#[no_mangle]
+pub extern "C" fn url_host(url: *URL, buffer: *mut c_char, capacity: size_t)
+                        -> size_t {
+    let host = unsafe { &*url }.host_str().unwrap_or(~"");
+    copy_to_buffer(host, buffer, capacity)
+}
+
+/// Write the port of `url` into `buffer`.
+#[no_mangle]
+pub extern "C" fn url_port(url: *URL, buffer: *mut c_char, capacity: size_t)
+                        -> size_t {
+    let port = unsafe { &*url }.port().unwrap_or(~"");
+    copy_to_buffer(port, buffer, capacity)
+}
+
+/// Write the query (without the leading `?`) of `url` into `buffer`.
+#[no_mangle]
+pub extern "C" fn url_query(url: *URL, buffer: *mut c_char, capacity: size_t)
+                         -> size_t {
+    let query = unsafe { &*url }.query().unwrap_or(~"");
+    copy_to_buffer(query, buffer, capacity)
+}
+
+/// Write the fragment (without the leading `#`) of `url` into `buffer`.
+#[no_mangle]
+pub extern "C" fn url_fragment(url: *URL, buffer: *mut c_char, capacity: size_t)
+                            -> size_t {
+    let fragment = unsafe { &*url }.fragment().unwrap_or(~"");
+    copy_to_buffer(fragment, buffer, capacity)
+}
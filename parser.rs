@@ -0,0 +1,271 @@
+// Copyright 2013 Simon Sapin.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The WHATWG "basic URL parser", producing the `URL` structure defined in the
+//! crate root.  `parse_url` is the single entry point used by `URL::parse`.
+
+use super::{URL, OtherSchemeData, RelativeSchemeData, SchemeRelativeURL,
+            UserInfo, Host, ParseResult};
+use super::{InvalidPort, ExpectedTwoSlashes, RelativeUrlWithoutBase,
+            RelativeUrlWithCannotBeABaseBase};
+use super::percent_encoding::{utf8_percent_encode, DefaultEncodeSet,
+                              QueryEncodeSet, SimpleEncodeSet,
+                              UserInfoEncodeSet};
+
+
+/// Parse `input`, resolving it against `base_url` when it is a relative
+/// reference.
+pub fn parse_url(input: &str, base_url: Option<&URL>) -> ParseResult<URL> {
+    // Strip leading and trailing whitespace and drop any tab or newline.
+    let cleaned = input.trim().replace("\t", "").replace("\n", "").replace("\r", "");
+    let input = cleaned.as_slice();
+    match parse_scheme(input) {
+        Some((scheme, rest)) => {
+            if is_relative_scheme(scheme.as_slice()) {
+                parse_relative(scheme, rest, base_url)
+            } else {
+                // Cannot-be-a-base URL: everything up to the query or fragment
+                // is kept verbatim.
+                let (data, query, fragment) = split_query_fragment(rest.as_slice());
+                Ok(URL {
+                    scheme: scheme,
+                    scheme_data: OtherSchemeData(data),
+                    query: query,
+                    fragment: fragment,
+                })
+            }
+        },
+        None => match base_url {
+            Some(base) => parse_relative_reference(input, base),
+            None => Err(RelativeUrlWithoutBase),
+        },
+    }
+}
+
+
+// Parse the scheme, returning the lower-cased scheme and the remainder after
+// the `:`.  Returns `None` when `input` does not start with a valid scheme.
+fn parse_scheme(input: &str) -> Option<(~str, ~str)> {
+    for (i, c) in input.char_indices() {
+        match c {
+            'a' .. 'z' | 'A' .. 'Z' => (),
+            '0' .. '9' | '+' | '-' | '.' if i > 0 => (),
+            ':' if i > 0 => {
+                let mut scheme = ~"";
+                for c in input.slice_to(i).chars() {
+                    scheme.push_char(c.to_lowercase())
+                }
+                return Some((scheme, input.slice_from(i + 1).to_owned()))
+            },
+            _ => return None,
+        }
+    }
+    None
+}
+
+
+fn is_relative_scheme(scheme: &str) -> bool {
+    match scheme {
+        "http" | "https" | "ws" | "wss" | "ftp" | "file" | "gopher" => true,
+        _ => false,
+    }
+}
+
+
+// Split the fragment and then the query off the end of a string, returning the
+// remainder unchanged and the percent-encoded query and fragment.
+fn split_query_fragment(input: &str) -> (~str, Option<~str>, Option<~str>) {
+    let (before_fragment, fragment) = match input.find('#') {
+        Some(i) => (input.slice_to(i),
+                    Some(utf8_percent_encode(input.slice_from(i + 1), SimpleEncodeSet))),
+        None => (input, None),
+    };
+    let (before_query, query) = match before_fragment.find('?') {
+        Some(i) => (before_fragment.slice_to(i),
+                    Some(utf8_percent_encode(before_fragment.slice_from(i + 1), QueryEncodeSet))),
+        None => (before_fragment, None),
+    };
+    (before_query.to_owned(), query, fragment)
+}
+
+
+fn parse_relative(scheme: ~str, rest: ~str, base_url: Option<&URL>)
+               -> ParseResult<URL> {
+    let (authority_and_path, query, fragment) = split_query_fragment(rest.as_slice());
+    let body = authority_and_path.as_slice();
+    // A backslash counts as a slash, as it does in the authority and path.
+    let is_slash = |i: uint| i < body.len() && (body[i] == '/' as u8 || body[i] == '\\' as u8);
+    if is_slash(0) && is_slash(1) {
+        let after = body.slice_from(2);
+        let (authority, path_str) = split_authority_path(after);
+        let (userinfo, host, port) = match parse_authority(authority) {
+            Ok(parts) => parts,
+            Err(error) => return Err(error),
+        };
+        Ok(URL {
+            scheme: scheme,
+            scheme_data: RelativeSchemeData(SchemeRelativeURL {
+                userinfo: userinfo,
+                host: host,
+                port: port,
+                path: parse_path(path_str),
+            }),
+            query: query,
+            fragment: fragment,
+        })
+    } else {
+        // No authority of its own: inherit one from a base with the same
+        // scheme, otherwise `//` was required.
+        match base_url.and_then(|base| base.relative_scheme_data()) {
+            Some(base_data) if scheme.as_slice() == base_url.unwrap().scheme.as_slice() => {
+                let path = if is_slash(0) {
+                    parse_path(body)
+                } else {
+                    merge_paths(base_data.path.as_slice(), body)
+                };
+                Ok(URL {
+                    scheme: scheme,
+                    scheme_data: RelativeSchemeData(SchemeRelativeURL {
+                        userinfo: base_data.userinfo.clone(),
+                        host: base_data.host.clone(),
+                        port: base_data.port.clone(),
+                        path: path,
+                    }),
+                    query: query,
+                    fragment: fragment,
+                })
+            },
+            _ => Err(ExpectedTwoSlashes),
+        }
+    }
+}
+
+
+// Resolve a scheme-less relative reference against `base`.
+fn parse_relative_reference(input: &str, base: &URL) -> ParseResult<URL> {
+    let base_data = match base.relative_scheme_data() {
+        Some(base_data) => base_data,
+        None => if input.len() == 0 {
+            return Ok(base.clone())
+        } else {
+            return Err(RelativeUrlWithCannotBeABaseBase)
+        },
+    };
+    if input.starts_with("//") || input.starts_with("/") || input.starts_with("\\") {
+        // A new authority or an absolute path: reuse the relative-scheme path.
+        return parse_relative(base.scheme.clone(), input.to_owned(), Some(base))
+    }
+    let (body, query, fragment) = split_query_fragment(input);
+    let body = body.as_slice();
+    // A bare query or fragment keeps the rest of the base untouched.
+    let (path, query) = if body.len() == 0 {
+        (base_data.path.clone(), if query.is_some() { query } else { base.query.clone() })
+    } else {
+        (merge_paths(base_data.path.as_slice(), body), query)
+    };
+    Ok(URL {
+        scheme: base.scheme.clone(),
+        scheme_data: RelativeSchemeData(SchemeRelativeURL {
+            userinfo: base_data.userinfo.clone(),
+            host: base_data.host.clone(),
+            port: base_data.port.clone(),
+            path: path,
+        }),
+        query: query,
+        fragment: fragment,
+    })
+}
+
+
+// Split an authority from the path that follows it (at the first `/` or `\`).
+fn split_authority_path<'a>(input: &'a str) -> (&'a str, &'a str) {
+    let mut end = input.len();
+    for (i, c) in input.char_indices() {
+        if c == '/' || c == '\\' {
+            end = i;
+            break
+        }
+    }
+    (input.slice_to(end), input.slice_from(end))
+}
+
+
+// Parse `userinfo@host:port`, percent-encoding the userinfo and handing the
+// host to `Host::parse`.
+fn parse_authority(authority: &str) -> ParseResult<(Option<UserInfo>, Host, ~str)> {
+    let (userinfo, host_port) = match authority.rfind('@') {
+        Some(i) => {
+            let credentials = authority.slice_to(i);
+            let (username, password) = match credentials.find(':') {
+                Some(j) => (credentials.slice_to(j), Some(credentials.slice_from(j + 1))),
+                None => (credentials, None),
+            };
+            (Some(UserInfo {
+                username: utf8_percent_encode(username, UserInfoEncodeSet),
+                password: password.map(|p| utf8_percent_encode(p, UserInfoEncodeSet)),
+            }), authority.slice_from(i + 1))
+        },
+        None => (None, authority),
+    };
+    // Find the port separator, skipping any `:` inside an IPv6 `[...]` literal.
+    let colon = if host_port.starts_with("[") {
+        match host_port.rfind(']') {
+            Some(close) => host_port.slice_from(close).find(':').map(|j| close + j),
+            None => None,
+        }
+    } else {
+        host_port.rfind(':')
+    };
+    let (host_str, port) = match colon {
+        Some(i) => (host_port.slice_to(i), host_port.slice_from(i + 1)),
+        None => (host_port, ""),
+    };
+    for c in port.chars() {
+        if !c.is_digit() {
+            return Err(InvalidPort)
+        }
+    }
+    match Host::parse(host_str) {
+        Ok(host) => Ok((userinfo, host, port.to_owned())),
+        Err(error) => Err(error),
+    }
+}
+
+
+// Parse an absolute path (starting with `/` or `\`), resolving `.` and `..`
+// and percent-encoding each segment.
+fn parse_path(input: &str) -> ~[~str] {
+    let normalized = input.replace("\\", "/");
+    let mut segments: ~[~str] = ~[];
+    let mut parts = normalized.split('/');
+    parts.next();  // drop the empty segment before the leading slash
+    for segment in parts {
+        match segment {
+            "." => (),
+            ".." => { segments.pop(); },
+            _ => segments.push(utf8_percent_encode(segment, DefaultEncodeSet)),
+        }
+    }
+    segments
+}
+
+
+// Merge a relative path against a base path per RFC 3986 section 5.2.3.
+fn merge_paths(base_path: &[~str], relative: &str) -> ~[~str] {
+    let mut segments = base_path.to_owned();
+    segments.pop();  // drop the base's last (file) segment
+    let normalized = relative.replace("\\", "/");
+    for segment in normalized.split('/') {
+        match segment {
+            "." => (),
+            ".." => { segments.pop(); },
+            _ => segments.push(utf8_percent_encode(segment, DefaultEncodeSet)),
+        }
+    }
+    segments
+}
@@ -0,0 +1,141 @@
+// Copyright 2013 Simon Sapin.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Percent-encoding and -decoding of byte strings, as used throughout the
+//! URL parser.  The encoding functions take an `EncodeSet` selecting which
+//! ASCII characters to escape on top of the bytes (C0 controls, DEL and non-ASCII)
+//! that are always escaped, so the same machinery serves hosts, userinfo,
+//! path segments and queries.
+
+use std::str;
+
+
+/// A set of ASCII characters to percent-encode, in addition to the bytes that
+/// are always encoded.  Every set encodes everything `SimpleEncodeSet` does,
+/// and every set but `SimpleEncodeSet` everything `DefaultEncodeSet` does.
+#[deriving(Eq, Clone)]
+pub enum EncodeSet {
+    /// Encode only the bytes that must always be encoded.
+    SimpleEncodeSet,
+    /// Additionally encode space, `"`, `#`, `<`, `>`, `?` and `` ` ``.
+    DefaultEncodeSet,
+    /// The default set plus `@`, for the userinfo component.
+    UserInfoEncodeSet,
+    /// The default set plus `@`, `/` and `\`, for a single path segment.
+    PathSegmentEncodeSet,
+    /// Same as the default set, for the query component.
+    QueryEncodeSet,
+    /// The path-segment set plus `:`, for a username.
+    UsernameEncodeSet,
+    /// The same as the path-segment set, for a password.
+    PasswordEncodeSet,
+}
+
+
+#[inline]
+fn should_encode(byte: u8, encode_set: EncodeSet) -> bool {
+    if byte < 0x20 || byte > 0x7E {
+        return true
+    }
+    match byte as char {
+        ' ' | '"' | '#' | '<' | '>' | '?' | '`' => match encode_set {
+            SimpleEncodeSet => false,
+            _ => true,
+        },
+        '@' => match encode_set {
+            UserInfoEncodeSet | PathSegmentEncodeSet
+            | UsernameEncodeSet | PasswordEncodeSet => true,
+            _ => false,
+        },
+        '/' | '\\' => match encode_set {
+            PathSegmentEncodeSet | UsernameEncodeSet | PasswordEncodeSet => true,
+            _ => false,
+        },
+        ':' => match encode_set {
+            UsernameEncodeSet => true,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+
+/// Percent-encode an arbitrary byte string with the given set.
+pub fn percent_encode(input: &[u8], encode_set: EncodeSet) -> ~str {
+    let mut output = ~"";
+    for &byte in input.iter() {
+        if should_encode(byte, encode_set) {
+            percent_encode_byte(byte, &mut output)
+        } else {
+            unsafe { str::raw::push_byte(&mut output, byte) }
+        }
+    }
+    output
+}
+
+
+/// Percent-encode the UTF-8 bytes of a string with the given set.
+#[inline]
+pub fn utf8_percent_encode(input: &str, encode_set: EncodeSet) -> ~str {
+    percent_encode(input.as_bytes(), encode_set)
+}
+
+
+/// Append the `%XX` escape for a single byte to `output`.
+#[inline]
+pub fn percent_encode_byte(byte: u8, output: &mut ~str) {
+    unsafe {
+        str::raw::push_bytes(output, [
+            '%' as u8, to_hex_upper(byte >> 4), to_hex_upper(byte & 0x0F)
+        ])
+    }
+}
+
+
+/// Percent-decode a byte string, leaving invalid `%` sequences untouched.
+pub fn percent_decode(input: &[u8]) -> ~[u8] {
+    let mut output = ~[];
+    let mut i = 0u;
+    while i < input.len() {
+        let c = input[i];
+        if c == ('%' as u8) && i + 2 < input.len() {
+            match (from_hex(input[i + 1]), from_hex(input[i + 2])) {
+                (Some(h), Some(l)) => {
+                    output.push(h * 0x10 + l);
+                    i += 3;
+                    continue
+                },
+                _ => (),
+            }
+        }
+
+        output.push(c);
+        i += 1;
+    }
+    output
+}
+
+
+#[inline]
+pub fn from_hex(byte: u8) -> Option<u8> {
+    match byte {
+        0x30 .. 0x39 => Some(byte - 0x30),  // 0..9
+        0x41 .. 0x46 => Some(byte + 10 - 0x41),  // A..F
+        0x61 .. 0x66 => Some(byte + 10 - 0x61),  // a..f
+        _ => None
+    }
+}
+
+#[inline]
+fn to_hex_upper(value: u8) -> u8 {
+    match value {
+        0 .. 9 => value + 0x30,
+        10 .. 15 => value - 10 + 0x41,
+        _ => fail!()
+    }
+}
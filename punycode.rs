@@ -0,0 +1,196 @@
+// Copyright 2013 Simon Sapin.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Punycode (RFC 3492) encoding and decoding, as used by IDNA to turn a
+//! Unicode domain label into its ASCII-compatible `xn--` form and back.
+
+use std::char;
+use std::str;
+use std::u32;
+
+
+// Bootstring parameters for Punycode, RFC 3492 section 5.
+static BASE: u32 = 36;
+static T_MIN: u32 = 1;
+static T_MAX: u32 = 26;
+static SKEW: u32 = 38;
+static DAMP: u32 = 700;
+static INITIAL_BIAS: u32 = 72;
+static INITIAL_N: u32 = 0x80;
+static DELIMITER: char = '-';
+
+
+#[inline]
+fn adapt(mut delta: u32, num_points: u32, first_time: bool) -> u32 {
+    delta /= if first_time { DAMP } else { 2 };
+    delta += delta / num_points;
+    let mut k = 0;
+    while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+        delta /= BASE - T_MIN;
+        k += BASE;
+    }
+    k + (((BASE - T_MIN + 1) * delta) / (delta + SKEW))
+}
+
+
+/// Decode a Punycode string (without the `xn--` prefix) to Unicode.
+///
+/// Return `None` on malformed input or overflow.  Overflow can only happen on
+/// inputs that take more than 63 encoded bytes, the DNS length limit for a
+/// domain name label.
+pub fn decode(input: &str) -> Option<~[char]> {
+    // Handle "basic" code points: they are copied verbatim, up to the last
+    // delimiter if there is one.
+    let (mut output, input) = match input.rfind(DELIMITER) {
+        None => (~[], input),
+        Some(position) => (
+            input.slice_to(position).chars().collect::<~[char]>(),
+            if position > 0 { input.slice_from(position + 1) } else { input }
+        )
+    };
+    let mut code_point = INITIAL_N;
+    let mut bias = INITIAL_BIAS;
+    let mut i = 0u32;
+    let mut iter = input.chars();
+    loop {
+        let previous_i = i;
+        let mut weight = 1;
+        let mut k = BASE;
+        let mut byte = match iter.next() {
+            None => break,
+            Some(c) => c,
+        };
+        // RFC 3492 section 3.2, decoding a generalized variable-length integer.
+        loop {
+            let digit = match byte {
+                byte @ '0' .. '9' => byte as u32 - '0' as u32 + 26,
+                byte @ 'A' .. 'Z' => byte as u32 - 'A' as u32,
+                byte @ 'a' .. 'z' => byte as u32 - 'a' as u32,
+                _ => return None
+            };
+            if digit > (u32::MAX - i) / weight {
+                return None  // Overflow
+            }
+            i += digit * weight;
+            let t = if k <= bias { T_MIN }
+                    else if k >= bias + T_MAX { T_MAX }
+                    else { k - bias };
+            if digit < t {
+                break
+            }
+            if weight > u32::MAX / (BASE - t) {
+                return None  // Overflow
+            }
+            weight *= BASE - t;
+            k += BASE;
+            byte = match iter.next() {
+                None => return None,  // End of input before the end of this delta
+                Some(c) => c,
+            };
+        }
+        let length = output.len() as u32;
+        bias = adapt(i - previous_i, length + 1, previous_i == 0);
+        if i / (length + 1) > u32::MAX - code_point {
+            return None  // Overflow
+        }
+        code_point += i / (length + 1);
+        i %= length + 1;
+        let c = match char::from_u32(code_point) {
+            Some(c) => c,
+            None => return None
+        };
+        output.insert(i as uint, c);
+        i += 1;
+    }
+    Some(output)
+}
+
+
+/// Encode the UTF-8 string `input` to Punycode (without the `xn--` prefix).
+///
+/// Return `None` on overflow, which can only happen on inputs that would take
+/// more than 63 encoded bytes, the DNS length limit for a domain name label.
+pub fn encode_str(input: &str) -> Option<~str> {
+    encode(input.chars().collect::<~[char]>())
+}
+
+
+/// Encode a slice of code points to Punycode (without the `xn--` prefix).
+///
+/// Return `None` on overflow, as for `encode_str`.
+pub fn encode(input: &[char]) -> Option<~str> {
+    // Handle "basic" code points: the ASCII ones are copied verbatim.
+    let basic: ~[u8] = input.iter().filter_map(|&c|
+        if c.is_ascii() { Some(c as u8) } else { None }
+    ).collect();
+    let mut output = str::from_utf8_owned(basic).unwrap();
+    let basic_length = output.len() as u32;
+    if basic_length > 0 {
+        output.push_char('-')
+    }
+    let mut code_point = INITIAL_N;
+    let mut delta = 0u32;
+    let mut bias = INITIAL_BIAS;
+    let mut processed = basic_length;
+    let input_length = input.len() as u32;
+    while processed < input_length {
+        // All code points below `code_point` have been handled already; find
+        // the next larger one.
+        let min_code_point = input.iter().map(|&c| c as u32)
+            .filter(|&c| c >= code_point).min().unwrap();
+        if min_code_point - code_point > (u32::MAX - delta) / (processed + 1) {
+            return None  // Overflow
+        }
+        // Advance the decoder's <code_point, i> state to <min_code_point, 0>.
+        delta += (min_code_point - code_point) * (processed + 1);
+        code_point = min_code_point;
+        for &c in input.iter() {
+            let c = c as u32;
+            if c < code_point {
+                delta += 1;
+                if delta == 0 {
+                    return None  // Overflow
+                }
+            }
+            if c == code_point {
+                // Represent delta as a generalized variable-length integer.
+                let mut q = delta;
+                let mut k = BASE;
+                loop {
+                    let t = if k <= bias { T_MIN }
+                            else if k >= bias + T_MAX { T_MAX }
+                            else { k - bias };
+                    if q < t {
+                        break
+                    }
+                    let value = t + ((q - t) % (BASE - t));
+                    output.push_char(value_to_digit(value));
+                    q = (q - t) / (BASE - t);
+                    k += BASE;
+                }
+                output.push_char(value_to_digit(q));
+                bias = adapt(delta, processed + 1, processed == basic_length);
+                delta = 0;
+                processed += 1;
+            }
+        }
+        delta += 1;
+        code_point += 1;
+    }
+    Some(output)
+}
+
+
+#[inline]
+fn value_to_digit(value: u32) -> char {
+    match value {
+        0 .. 25 => (value as u8 + 'a' as u8) as char,  // a..z
+        26 .. 35 => (value as u8 - 26 + '0' as u8) as char,  // 0..9
+        _ => fail!()
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright 2013 Simon Sapin.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::{URL, Host};
+use super::{parse_ipv4number, parse_ipv4addr, ends_in_a_number};
+use super::{InvalidIpv4Address, InvalidPort};
+
+
+#[test]
+fn test_parse_ipv4number_radix() {
+    // Decimal, octal (leading zero) and hexadecimal (`0x`) inference.
+    assert_eq!(parse_ipv4number("255"), Some(255));
+    assert_eq!(parse_ipv4number("0177"), Some(127));
+    assert_eq!(parse_ipv4number("0x7f"), Some(127));
+    assert_eq!(parse_ipv4number("0X7F"), Some(127));
+    assert_eq!(parse_ipv4number("0"), Some(0));
+    assert_eq!(parse_ipv4number("x"), None);
+    assert_eq!(parse_ipv4number("0x"), None);
+    assert_eq!(parse_ipv4number(""), None);
+}
+
+#[test]
+fn test_parse_ipv4addr_dotted() {
+    assert_eq!(parse_ipv4addr("192.168.0.1"),
+               Ok(192 << 24 | 168 << 16 | 1));
+    // The non-decimal radixes compose the same address.
+    assert_eq!(parse_ipv4addr("0xc0.0250.0.1"),
+               Ok(192 << 24 | 168 << 16 | 1));
+}
+
+#[test]
+fn test_parse_ipv4addr_fewer_parts() {
+    // The final part fills every remaining low byte.
+    assert_eq!(parse_ipv4addr("127.1"), Ok(127 << 24 | 1));
+    assert_eq!(parse_ipv4addr("192.168.257"), Ok(192 << 24 | 168 << 16 | 257));
+    assert_eq!(parse_ipv4addr("16909060"), Ok(0x01020304));
+}
+
+#[test]
+fn test_parse_ipv4addr_overflow() {
+    // Each non-final part is bounded by 255 ...
+    assert_eq!(parse_ipv4addr("256.0.0.1"), Err(InvalidIpv4Address));
+    // ... and the final part by 256^(5 - N).
+    assert_eq!(parse_ipv4addr("192.168.0.256"), Err(InvalidIpv4Address));
+    assert_eq!(parse_ipv4addr("192.168.65536"), Err(InvalidIpv4Address));
+}
+
+#[test]
+fn test_parse_ipv4addr_errors() {
+    assert_eq!(parse_ipv4addr("1.2.3.4.5"), Err(InvalidIpv4Address));
+    assert_eq!(parse_ipv4addr("1.2.x"), Err(InvalidIpv4Address));
+    // A single trailing dot is dropped.
+    assert_eq!(parse_ipv4addr("192.168.0.1."), Ok(192 << 24 | 168 << 16 | 1));
+}
+
+#[test]
+fn test_ends_in_a_number() {
+    assert!(ends_in_a_number([~"1", ~"2", ~"3", ~"4"]));
+    assert!(ends_in_a_number([~"0x1f"]));
+    // A single trailing empty label (a trailing dot) is ignored.
+    assert!(ends_in_a_number([~"192", ~"168", ~"0", ~"1", ~""]));
+    assert!(!ends_in_a_number([~"example", ~"com"]));
+    assert!(!ends_in_a_number([~"0x"]));
+}
+
+#[test]
+fn test_host_ipv4_roundtrip() {
+    assert_eq!(Host::parse("192.168.0.1").unwrap().serialize(), ~"192.168.0.1");
+    assert_eq!(Host::parse("0x7f.1").unwrap().serialize(), ~"127.0.0.1");
+    assert_eq!(Host::parse("example.com").unwrap().serialize(), ~"example.com");
+}
+
+#[test]
+fn test_url_roundtrip() {
+    assert_eq!(URL::parse("http://example.com/", None).unwrap().serialize(),
+               ~"http://example.com/");
+    assert_eq!(URL::parse("http://example.com:8080/a/b", None).unwrap().serialize(),
+               ~"http://example.com:8080/a/b");
+}
+
+#[test]
+fn test_origin_tuple() {
+    let a = URL::parse("http://example.com/a", None).unwrap().origin();
+    let b = URL::parse("http://example.com/b", None).unwrap().origin();
+    assert!(a.is_same_origin(&b));
+    assert_eq!(a.ascii_serialization(), ~"http://example.com");
+    let c = URL::parse("http://example.com:8080/", None).unwrap().origin();
+    assert!(!a.is_same_origin(&c));
+    assert_eq!(c.ascii_serialization(), ~"http://example.com:8080");
+}
+
+#[test]
+fn test_origin_opaque() {
+    let o = URL::parse("data:text/plain,hi", None).unwrap().origin();
+    // Reflexive: an opaque origin is the same origin as itself (and its clone).
+    assert!(o.is_same_origin(&o));
+    assert!(o.is_same_origin(&o.clone()));
+    assert_eq!(o.ascii_serialization(), ~"null");
+    // Two independently-minted opaque origins are never the same.
+    let p = URL::parse("data:text/plain,hi", None).unwrap().origin();
+    assert!(!o.is_same_origin(&p));
+}
+
+#[test]
+fn test_url_port_or_default() {
+    assert_eq!(URL::parse("http://example.com/", None).unwrap().port_or_default(),
+               Ok(80));
+    assert_eq!(URL::parse("https://example.com/", None).unwrap().port_or_default(),
+               Ok(443));
+    assert_eq!(URL::parse("http://example.com:99999/", None).unwrap().port_or_default(),
+               Err(InvalidPort));
+}
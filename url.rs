@@ -17,6 +17,9 @@ extern mod encoding;
 #[cfg(test)]
 extern mod extra;
 
+use std::io;
+use std::io::net::addrinfo;
+use std::io::net::ip::{SocketAddr, Ipv4Addr, Ipv6Addr};
 use std::str;
 
 use encoding::EncodingRef;
@@ -26,8 +29,15 @@ use encoding::label::encoding_from_whatwg_label;
 
 
 pub mod punycode;
+pub mod percent_encoding;
+pub mod ffi;
 mod parser;
 
+use percent_encoding::{SimpleEncodeSet, DefaultEncodeSet, QueryEncodeSet,
+                       UsernameEncodeSet, PasswordEncodeSet};
+use percent_encoding::{utf8_percent_encode, percent_encode_byte,
+                       percent_decode, from_hex};
+
 #[cfg(test)]
 mod tests;
 
@@ -63,6 +73,7 @@ pub struct UserInfo {
 #[deriving(Clone)]
 pub enum Host {
     Domain(~[~str]),
+    Ipv4(u32),
     IPv6(IPv6Address)
 }
 
@@ -70,6 +81,15 @@ pub struct IPv6Address {
     pieces: [u16, ..8]
 }
 
+#[deriving(Clone)]
+pub enum Origin {
+    /// A tuple origin: scheme, host and port.
+    TupleOrigin(~str, Host, ~str),
+    /// An opaque (unique) origin, identified by a process-unique id so that it
+    /// is the same origin as itself but never as an independently-minted one.
+    OpaqueOrigin(uint),
+}
+
 impl Clone for IPv6Address {
     fn clone(&self) -> IPv6Address {
         IPv6Address { pieces: self.pieces }
@@ -77,14 +97,50 @@ impl Clone for IPv6Address {
 }
 
 
-macro_rules! is_match(
-    ($value:expr, $($pattern:pat)|+) => (
-        match $value { $($pattern)|+ => true, _ => false }
-    );
-)
+#[deriving(Eq, Clone)]
+pub enum ParseError {
+    EmptyHost,
+    InvalidScheme,
+    InvalidPort,
+    InvalidIpv4Address,
+    InvalidIpv6Address,
+    InvalidDomainCharacter,
+    InvalidCharacter,
+    InvalidBackslash,
+    InvalidPercentEncoded,
+    InvalidAtSymbolInUser,
+    ExpectedTwoSlashes,
+    ExpectedInitialSlash,
+    IdnaError,
+    RelativeUrlWithCannotBeABaseBase,
+    RelativeUrlWithoutBase,
+}
+
+impl ParseError {
+    pub fn description(&self) -> &'static str {
+        match *self {
+            EmptyHost => "Empty host",
+            InvalidScheme => "Invalid scheme",
+            InvalidPort => "Invalid port number",
+            InvalidIpv4Address => "Invalid IPv4 address",
+            InvalidIpv6Address => "Invalid IPv6 address",
+            InvalidDomainCharacter => "Invalid domain character",
+            InvalidCharacter => "Invalid character",
+            InvalidBackslash => "Invalid backslash",
+            InvalidPercentEncoded => "Invalid percent-encoded sequence",
+            InvalidAtSymbolInUser => "Invalid @-symbol in user and password",
+            ExpectedTwoSlashes => "Expected two slashes (//)",
+            ExpectedInitialSlash => "Expected the input to start with a slash",
+            IdnaError => "Invalid internationalized domain name",
+            RelativeUrlWithCannotBeABaseBase
+            => "Relative URL with a cannot-be-a-base base",
+            RelativeUrlWithoutBase => "Relative URL without a base",
+        }
+    }
+}
 
 
-pub type ParseResult<T> = Result<T, &'static str>;
+pub type ParseResult<T> = Result<T, ParseError>;
 
 
 impl URL {
@@ -92,6 +148,25 @@ impl URL {
         parser::parse_url(input, base_url)
     }
 
+    pub fn origin(&self) -> Origin {
+        match self.scheme_data {
+            RelativeSchemeData(ref scheme_data) => match default_port(self.scheme) {
+                Some(default) => {
+                    let port = if scheme_data.port.len() > 0 {
+                        scheme_data.port.to_owned()
+                    } else {
+                        default.to_owned()
+                    };
+                    TupleOrigin(self.scheme.to_owned(), scheme_data.host.clone(), port)
+                },
+                // Relative schemes without a default port (e.g. `file`)
+                // get a fresh opaque origin.
+                None => OpaqueOrigin(fresh_opaque_origin_id()),
+            },
+            OtherSchemeData(..) => OpaqueOrigin(fresh_opaque_origin_id()),
+        }
+    }
+
     pub fn serialize(&self) -> ~str {
         let mut result = self.serialize_no_fragment();
         match self.fragment {
@@ -155,41 +230,301 @@ impl URL {
 }
 
 
+impl URL {
+    fn relative_scheme_data<'a>(&'a self) -> Option<&'a SchemeRelativeURL> {
+        match self.scheme_data {
+            RelativeSchemeData(ref scheme_data) => Some(scheme_data),
+            OtherSchemeData(..) => None,
+        }
+    }
+
+    fn relative_scheme_data_mut<'a>(&'a mut self) -> Option<&'a mut SchemeRelativeURL> {
+        match self.scheme_data {
+            RelativeSchemeData(ref mut scheme_data) => Some(scheme_data),
+            OtherSchemeData(..) => None,
+        }
+    }
+
+    pub fn host_str(&self) -> Option<~str> {
+        self.relative_scheme_data().map(|scheme_data| scheme_data.host.serialize())
+    }
+
+    pub fn set_host(&mut self, host: Option<&str>) -> ParseResult<()> {
+        let new_host = match host {
+            Some(host) => match Host::parse(host) {
+                Ok(host) => host,
+                Err(error) => return Err(error),
+            },
+            None => Domain(~[]),
+        };
+        match self.relative_scheme_data_mut() {
+            Some(scheme_data) => { scheme_data.host = new_host; Ok(()) },
+            None => Err(RelativeUrlWithCannotBeABaseBase),
+        }
+    }
+
+    pub fn port(&self) -> Option<~str> {
+        self.relative_scheme_data().map(|scheme_data| scheme_data.port.to_owned())
+    }
+
+    pub fn set_port(&mut self, port: Option<u16>) -> ParseResult<()> {
+        match self.relative_scheme_data_mut() {
+            Some(scheme_data) => {
+                scheme_data.port = match port {
+                    Some(port) => port.to_str(),
+                    None => ~"",
+                };
+                Ok(())
+            },
+            None => Err(RelativeUrlWithCannotBeABaseBase),
+        }
+    }
+
+    pub fn path_segments<'a>(&'a self) -> Option<&'a [~str]> {
+        self.relative_scheme_data().map(|scheme_data| scheme_data.path.as_slice())
+    }
+
+    pub fn set_path(&mut self, path: &str) -> ParseResult<()> {
+        match self.relative_scheme_data_mut() {
+            Some(scheme_data) => {
+                // Strip at most one leading slash, so that e.g. `//foo`
+                // keeps its empty leading segment rather than silently losing
+                // a separator.
+                let path = if path.starts_with("/") { path.slice_from(1) } else { path };
+                let mut segments = ~[];
+                for segment in path.split('/') {
+                    segments.push(utf8_percent_encode(segment, DefaultEncodeSet));
+                }
+                scheme_data.path = segments;
+                Ok(())
+            },
+            None => Err(RelativeUrlWithCannotBeABaseBase),
+        }
+    }
+
+    pub fn username(&self) -> Option<~str> {
+        self.relative_scheme_data().map(|scheme_data| match scheme_data.userinfo {
+            Some(ref userinfo) => userinfo.username.to_owned(),
+            None => ~"",
+        })
+    }
+
+    pub fn set_username(&mut self, username: &str) -> ParseResult<()> {
+        let encoded = utf8_percent_encode(username, UsernameEncodeSet);
+        match self.relative_scheme_data_mut() {
+            Some(scheme_data) => {
+                match scheme_data.userinfo {
+                    Some(ref mut userinfo) => userinfo.username = encoded,
+                    None => scheme_data.userinfo = Some(UserInfo {
+                        username: encoded, password: None
+                    }),
+                }
+                Ok(())
+            },
+            None => Err(RelativeUrlWithCannotBeABaseBase),
+        }
+    }
+
+    pub fn password(&self) -> Option<~str> {
+        match self.relative_scheme_data() {
+            Some(scheme_data) => match scheme_data.userinfo {
+                Some(ref userinfo) => userinfo.password.clone(),
+                None => None,
+            },
+            None => None,
+        }
+    }
+
+    pub fn set_password(&mut self, password: Option<&str>) -> ParseResult<()> {
+        let password = password.map(|password|
+            utf8_percent_encode(password, PasswordEncodeSet));
+        match self.relative_scheme_data_mut() {
+            Some(scheme_data) => {
+                match scheme_data.userinfo {
+                    Some(ref mut userinfo) => userinfo.password = password,
+                    None => scheme_data.userinfo = Some(UserInfo {
+                        username: ~"", password: password
+                    }),
+                }
+                Ok(())
+            },
+            None => Err(RelativeUrlWithCannotBeABaseBase),
+        }
+    }
+
+    pub fn query(&self) -> Option<~str> {
+        self.query.clone()
+    }
+
+    pub fn set_query(&mut self, query: Option<&str>) -> ParseResult<()> {
+        self.query = query.map(|query| utf8_percent_encode(query, QueryEncodeSet));
+        Ok(())
+    }
+
+    pub fn fragment(&self) -> Option<~str> {
+        self.fragment.clone()
+    }
+
+    pub fn set_fragment(&mut self, fragment: Option<&str>) -> ParseResult<()> {
+        self.fragment = fragment.map(|fragment|
+            utf8_percent_encode(fragment, SimpleEncodeSet));
+        Ok(())
+    }
+
+    /// The port to connect to: the explicit port, or the scheme's default
+    /// port when none is given.  Cannot-be-a-base URLs and schemes without a
+    /// default port are reported through the structured `ParseError`.
+    pub fn port_or_default(&self) -> ParseResult<u16> {
+        let scheme_data = match self.relative_scheme_data() {
+            Some(scheme_data) => scheme_data,
+            None => return Err(RelativeUrlWithCannotBeABaseBase),
+        };
+        if scheme_data.port.len() > 0 {
+            match from_str::<u16>(scheme_data.port.as_slice()) {
+                Some(port) => Ok(port),
+                None => Err(InvalidPort),
+            }
+        } else {
+            match default_port(self.scheme) {
+                Some(port) => Ok(from_str::<u16>(port).unwrap()),
+                None => Err(InvalidPort),
+            }
+        }
+    }
+
+    /// Resolve this URL's host to concrete socket addresses, using its
+    /// explicit port or the scheme's default port.  Cannot-be-a-base URLs and
+    /// hosts with neither an explicit nor a default port are an error.
+    pub fn to_socket_addrs(&self) -> io::IoResult<~[SocketAddr]> {
+        let port = match self.port_or_default() {
+            Ok(port) => port,
+            // Surface the structured error's own description rather than a
+            // fresh string literal.
+            Err(error) => return Err(io::IoError {
+                kind: io::InvalidInput,
+                desc: error.description(),
+                detail: None,
+            }),
+        };
+        self.relative_scheme_data().unwrap().host.to_socket_addrs(port)
+    }
+}
+
+
+impl Origin {
+    pub fn is_same_origin(&self, other: &Origin) -> bool {
+        *self == *other
+    }
+
+    pub fn ascii_serialization(&self) -> ~str {
+        match *self {
+            OpaqueOrigin(..) => ~"null",
+            TupleOrigin(ref scheme, ref host, ref port) => {
+                let mut result = scheme.to_owned();
+                result.push_str("://");
+                result.push_str(host.serialize());
+                // The default port is left implicit.
+                if default_port(scheme.as_slice()) != Some(port.as_slice()) {
+                    result.push_str(":");
+                    result.push_str(port.as_slice());
+                }
+                result
+            }
+        }
+    }
+}
+
+impl Eq for Origin {
+    fn eq(&self, other: &Origin) -> bool {
+        match (self, other) {
+            (&TupleOrigin(ref scheme, ref host, ref port),
+             &TupleOrigin(ref other_scheme, ref other_host, ref other_port))
+            => scheme == other_scheme
+                && host.serialize() == other_host.serialize()
+                && port == other_port,
+            // Two opaque origins are the same only when they carry the same
+            // id, so a value is always the same origin as itself.
+            (&OpaqueOrigin(id), &OpaqueOrigin(other_id)) => id == other_id,
+            _ => false,
+        }
+    }
+}
+
+
+// A process-unique id for each freshly-minted opaque origin.
+fn fresh_opaque_origin_id() -> uint {
+    use std::unstable::atomics::{AtomicUint, INIT_ATOMIC_UINT, SeqCst};
+    static mut next_id: AtomicUint = INIT_ATOMIC_UINT;
+    unsafe { next_id.fetch_add(1, SeqCst) }
+}
+
+
+// The default port of a special scheme, or `None` for schemes (such as `file`)
+// that have none.
+fn default_port(scheme: &str) -> Option<&'static str> {
+    match scheme {
+        "http" | "ws" => Some("80"),
+        "https" | "wss" => Some("443"),
+        "ftp" => Some("21"),
+        _ => None,
+    }
+}
+
+
 impl Host {
     pub fn parse(input: &str) -> ParseResult<Host> {
         if input.len() == 0 {
-            Err("Empty host")
+            Err(EmptyHost)
         } else if input[0] == '[' as u8 {
             if input[input.len() - 1] == ']' as u8 {
-                match IPv6Address::parse(input.slice(1, input.len() - 1)) {
-                    Some(address) => Ok(IPv6(address)),
-                    None => Err("Invalid IPv6 address"),
-                }
+                IPv6Address::parse(input.slice(1, input.len() - 1)).map(IPv6)
             } else {
-                Err("Invalid IPv6 address")
+                Err(InvalidIpv6Address)
             }
         } else {
-            let mut percent_encoded = ~"";
-            utf8_percent_encode(input, SimpleEncodeSet, &mut percent_encoded);
+            let percent_encoded = utf8_percent_encode(input, SimpleEncodeSet);
             let bytes = percent_decode(percent_encoded.as_bytes());
             let decoded = UTF_8.decode(bytes, encoding::DecodeReplace).unwrap();
+            // Case-fold the whole domain (lower-case every code point) before
+            // splitting it into labels, so that labels differing only in case
+            // map to the same ASCII form.
+            let mut folded = ~"";
+            for c in decoded.chars() {
+                folded.push_char(c.to_lowercase())
+            }
             let mut labels = ~[];
-            for label in decoded.split(&['.', '\u3002', '\uFF0E', '\uFF61']) {
-                // TODO: Remove this check and use IDNA "domain to ASCII"
-                // TODO: switch to .map(domain_label_to_ascii).collect() then.
-                if label.is_ascii() {
-                    labels.push(label.to_owned())
-                } else {
-                    return Err("Non-ASCII domains (IDNA) are not supported yet.")
+            for label in folded.split(&['.', '\u3002', '\uFF0E', '\uFF61']) {
+                match domain_label_to_ascii(label) {
+                    Ok(label) => labels.push(label),
+                    Err(message) => return Err(message),
                 }
             }
-            Ok(Domain(labels))
+            // An authority whose last label looks like a number is not a
+            // domain but a (possibly non-dotted-decimal) IPv4 address.
+            if ends_in_a_number(labels.as_slice()) {
+                parse_ipv4addr(labels.connect(".")).map(Ipv4)
+            } else {
+                Ok(Domain(labels))
+            }
         }
     }
 
     pub fn serialize(&self) -> ~str {
         match *self {
             Domain(ref labels) => labels.connect("."),
+            Ipv4(address) => {
+                let mut result = ~"";
+                let mut shift = 24;
+                loop {
+                    result.push_str(((address >> shift) & 0xFF).to_str());
+                    if shift == 0 {
+                        break
+                    }
+                    result.push_str(".");
+                    shift -= 8;
+                }
+                result
+            },
             IPv6(ref address) => {
                 let mut result = ~"[";
                 result.push_str(address.serialize());
@@ -198,11 +533,145 @@ impl Host {
             }
         }
     }
+
+    /// Resolve this host, combined with `port`, to concrete socket addresses.
+    /// IP hosts resolve without DNS; `Domain` hosts go through the system
+    /// name resolver.
+    pub fn to_socket_addrs(&self, port: u16) -> io::IoResult<~[SocketAddr]> {
+        match *self {
+            Ipv4(address) => Ok(~[SocketAddr {
+                ip: Ipv4Addr((address >> 24) as u8, (address >> 16) as u8,
+                             (address >> 8) as u8, address as u8),
+                port: port,
+            }]),
+            IPv6(ref address) => {
+                let p = &address.pieces;
+                Ok(~[SocketAddr {
+                    ip: Ipv6Addr(p[0], p[1], p[2], p[3], p[4], p[5], p[6], p[7]),
+                    port: port,
+                }])
+            },
+            Domain(ref labels) => {
+                let host = labels.connect(".");
+                let addresses = match addrinfo::get_host_addresses(host.as_slice()) {
+                    Ok(addresses) => addresses,
+                    Err(error) => return Err(error),
+                };
+                Ok(addresses.move_iter()
+                   .map(|ip| SocketAddr { ip: ip, port: port })
+                   .collect())
+            },
+        }
+    }
+}
+
+
+// IDNA "domain to ASCII", applied to a single label.  The domain has already
+// been case-folded (lower-cased) before being split into labels, so an ASCII
+// label is kept verbatim and any other label is Punycode-encoded and given the
+// `xn--` ACE prefix.  Full NFC normalization is not performed.
+fn domain_label_to_ascii(label: &str) -> ParseResult<~str> {
+    if label.is_ascii() {
+        // ASCII labels are kept verbatim.  The empty label produced by a
+        // trailing dot (e.g. in `example.com.`) is legitimate.
+        if label.len() > 63 {
+            return Err(IdnaError)
+        }
+        // A label that already carries the ACE prefix must Punycode-decode back.
+        if label.starts_with("xn--") && punycode::decode(label.slice_from(4)).is_none() {
+            return Err(IdnaError)
+        }
+        return Ok(label.to_owned())
+    }
+    // A non-ASCII label is Punycode-encoded and given the ACE prefix; only a
+    // non-empty label that encodes to nothing (or overflows the DNS limit) is
+    // an error.
+    let ascii = match punycode::encode_str(label) {
+        Some(encoded) => ~"xn--" + encoded,
+        None => return Err(IdnaError),
+    };
+    if ascii.len() == 4 || ascii.len() > 63 {
+        return Err(IdnaError)
+    }
+    Ok(ascii)
+}
+
+
+// Whether a host's last non-empty label is to be parsed as a number, in which
+// case the whole host is an IPv4 address rather than a domain.
+fn ends_in_a_number(labels: &[~str]) -> bool {
+    let last = match labels.last() {
+        // One trailing empty label (a trailing dot) is ignored.
+        Some(&ref label) if label.len() == 0 => match labels.slice_to(labels.len() - 1).last() {
+            Some(&ref label) => label.as_slice(),
+            None => return false,
+        },
+        Some(&ref label) => label.as_slice(),
+        None => return false,
+    };
+    if last.len() > 0 && last.chars().all(|c| c.is_digit()) {
+        return true
+    }
+    if last.len() > 2 && (last.starts_with("0x") || last.starts_with("0X")) {
+        return last.slice_from(2).chars().all(|c| c.is_digit_radix(16))
+    }
+    false
+}
+
+
+// Parse one dotted part of an IPv4 address, inferring the radix from its
+// prefix: `0x`/`0X` is hexadecimal, a leading `0` is octal, anything else
+// decimal.
+fn parse_ipv4number(input: &str) -> Option<u32> {
+    if input.starts_with("0x") || input.starts_with("0X") {
+        std::num::from_str_radix(input.slice_from(2), 16)
+    } else if input.len() >= 2 && input[0] == '0' as u8 {
+        std::num::from_str_radix(input.slice_from(1), 8)
+    } else {
+        std::num::from_str_radix(input, 10)
+    }
+}
+
+
+// https://url.spec.whatwg.org/#concept-ipv4-parser
+fn parse_ipv4addr(input: &str) -> ParseResult<u32> {
+    let mut parts: ~[&str] = input.split('.').collect();
+    match parts.last() {
+        Some(&"") => { parts.pop(); },
+        _ => (),
+    }
+    if parts.len() > 4 {
+        return Err(InvalidIpv4Address)
+    }
+    let mut numbers = ~[];
+    for part in parts.iter() {
+        match parse_ipv4number(*part) {
+            Some(number) => numbers.push(number),
+            None => return Err(InvalidIpv4Address),
+        }
+    }
+    // Every part but the last occupies a single high-order byte.
+    let last = numbers.pop().unwrap();
+    for &number in numbers.iter() {
+        if number > 255 {
+            return Err(InvalidIpv4Address)
+        }
+    }
+    // The last part fills the bytes the earlier parts left over; with no
+    // earlier parts it may span the whole 32-bit space.
+    if numbers.len() > 0 && last >= (1u32 << (8 * (4 - numbers.len()))) {
+        return Err(InvalidIpv4Address)
+    }
+    let mut address = last;
+    for (counter, &number) in numbers.iter().enumerate() {
+        address += number << (8 * (3 - counter));
+    }
+    Ok(address)
 }
 
 
 impl IPv6Address {
-    pub fn parse(input: &str) -> Option<IPv6Address> {
+    pub fn parse(input: &str) -> ParseResult<IPv6Address> {
         let len = input.len();
         let mut is_ip_v4 = false;
         let mut pieces = [0, 0, 0, 0, 0, 0, 0, 0];
@@ -211,7 +680,7 @@ impl IPv6Address {
         let mut i = 0u;
         if input[0] == ':' as u8 {
             if input[1] != ':' as u8 {
-                return None
+                return Err(InvalidIpv6Address)
             }
             i = 2;
             piece_pointer = 1;
@@ -220,11 +689,11 @@ impl IPv6Address {
 
         while i < len {
             if piece_pointer == 8 {
-                return None
+                return Err(InvalidIpv6Address)
             }
             if input[i] == ':' as u8 {
                 if compress_pointer.is_some() {
-                    return None
+                    return Err(InvalidIpv6Address)
                 }
                 i += 1;
                 piece_pointer += 1;
@@ -247,7 +716,7 @@ impl IPv6Address {
                 match input[i] as char {
                     '.' => {
                         if i == start {
-                            return None
+                            return Err(InvalidIpv6Address)
                         }
                         i = start;
                         is_ip_v4 = true;
@@ -255,10 +724,10 @@ impl IPv6Address {
                     ':' => {
                         i += 1;
                         if i == len {
-                            return None
+                            return Err(InvalidIpv6Address)
                         }
                     },
-                    _ => return None
+                    _ => return Err(InvalidIpv6Address)
                 }
             }
             if is_ip_v4 {
@@ -270,7 +739,7 @@ impl IPv6Address {
 
         if is_ip_v4 {
             if piece_pointer > 6 {
-                return None
+                return Err(InvalidIpv6Address)
             }
             let mut dots_seen = 0u;
             while i < len {
@@ -282,11 +751,11 @@ impl IPv6Address {
                     };
                     value = value * 10 + digit as u16;
                     if value > 255 {
-                        return None
+                        return Err(InvalidIpv6Address)
                     }
                 }
                 if dots_seen < 3 && !(i < len && input[i] == '.' as u8) {
-                    return None
+                    return Err(InvalidIpv6Address)
                 }
                 pieces[piece_pointer] = pieces[piece_pointer] * 0x100 + value;
                 if dots_seen == 0 || dots_seen == 2 {
@@ -294,7 +763,7 @@ impl IPv6Address {
                 }
                 i += 1;
                 if dots_seen == 3 && i < len {
-                    return None
+                    return Err(InvalidIpv6Address)
                 }
                 dots_seen += 1;
             }
@@ -312,10 +781,10 @@ impl IPv6Address {
                 }
             }
             _ => if piece_pointer != 8 {
-                return None
+                return Err(InvalidIpv6Address)
             }
         }
-        Some(IPv6Address { pieces: pieces })
+        Ok(IPv6Address { pieces: pieces })
     }
 
     pub fn serialize(&self) -> ~str {
@@ -375,95 +844,6 @@ fn longest_zero_sequence(pieces: &[u16, ..8]) -> (int, int) {
 }
 
 
-#[inline]
-fn from_hex(byte: u8) -> Option<u8> {
-    match byte {
-        0x30 .. 0x39 => Some(byte - 0x30),  // 0..9
-        0x41 .. 0x46 => Some(byte + 10 - 0x41),  // A..F
-        0x61 .. 0x66 => Some(byte + 10 - 0x61),  // a..f
-        _ => None
-    }
-}
-
-#[inline]
-fn to_hex_upper(value: u8) -> u8 {
-    match value {
-        0 .. 9 => value + 0x30,
-        10 .. 15 => value - 10 + 0x41,
-        _ => fail!()
-    }
-}
-
-
-enum EncodeSet {
-    SimpleEncodeSet,
-    DefaultEncodeSet,
-    UserInfoEncodeSet,
-    PasswordEncodeSet,
-    UsernameEncodeSet
-}
-
-
-#[inline]
-fn utf8_percent_encode(input: &str, encode_set: EncodeSet, output: &mut ~str) {
-    use Default = self::DefaultEncodeSet;
-    use UserInfo = self::UserInfoEncodeSet;
-    use Password = self::PasswordEncodeSet;
-    use Username = self::UsernameEncodeSet;
-    for byte in input.bytes() {
-        if byte < 0x20 || byte > 0x7E || match byte as char {
-            ' ' | '"' | '#' | '<' | '>' | '?' | '`'
-            => is_match!(encode_set, Default | UserInfo | Password | Username),
-            '@'
-            => is_match!(encode_set, UserInfo | Password | Username),
-            '/' | '\\'
-            => is_match!(encode_set, Password | Username),
-            ':'
-            => is_match!(encode_set, Username),
-            _ => false,
-        } {
-            percent_encode_byte(byte, output)
-        } else {
-            unsafe { str::raw::push_byte(output, byte) }
-        }
-    }
-}
-
-
-#[inline]
-fn percent_encode_byte(byte: u8, output: &mut ~str) {
-    unsafe {
-        str::raw::push_bytes(output, [
-            '%' as u8, to_hex_upper(byte >> 4), to_hex_upper(byte & 0x0F)
-        ])
-    }
-}
-
-
-#[inline]
-fn percent_decode(input: &[u8]) -> ~[u8] {
-    let mut output = ~[];
-    let mut i = 0u;
-    while i < input.len() {
-        let c = input[i];
-        if c == ('%' as u8) && i + 2 < input.len() {
-            match (from_hex(input[i + 1]), from_hex(input[i + 2])) {
-                (Some(h), Some(l)) => {
-                    output.push(h * 0x10 + l);
-                    i += 3;
-                    continue
-                },
-                _ => (),
-            }
-        }
-
-        output.push(c);
-        i += 1;
-    }
-    output
-}
-
-
 pub fn parse_form_urlencoded(input: &str,
                              encoding_override: Option<EncodingRef>,
                              use_charset: bool,